@@ -16,9 +16,13 @@
 // to the Bonsai proving service and publish the received proofs directly
 // to your deployed app contract.
 
-use alloy_primitives::{Address, U256};
-use anyhow::{ensure, Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use alloy_primitives::{keccak256, Address, B256, U256};
+use anyhow::{bail, ensure, Context, Result};
 use clap::Parser;
+use serde_json::json;
 use rewards_methods::{BALANCE_OF_ELF, BALANCE_OF_ID};
 use risc0_ethereum_contracts::encode_seal;
 use risc0_steel::alloy::{
@@ -29,7 +33,10 @@ use risc0_steel::alloy::{
     sol_types::{SolCall, SolValue},
 };
 use risc0_steel::{
-    ethereum::{EthEvmEnv, ETH_SEPOLIA_CHAIN_SPEC},
+    ethereum::{
+        EthChainSpec, EthEvmEnv, ETH_HOLESKY_CHAIN_SPEC, ETH_MAINNET_CHAIN_SPEC,
+        ETH_SEPOLIA_CHAIN_SPEC,
+    },
     host::BlockNumberOrTag,
     Commitment, Contract,
 };
@@ -51,9 +58,16 @@ sol! {
         function delegates(address account) external view returns (address);
     }
 
+    /// ERC-5805 checkpointed voting power (as implemented by OpenZeppelin `ERC20Votes`).
+    interface IVotes {
+        function getPastVotes(address account, uint256 timepoint) external view returns (uint256);
+        function getPastTotalSupply(uint256 timepoint) external view returns (uint256);
+    }
+
     interface IProposal {
         function votingToken() external view returns (address);
         function votedAt(uint256 proposalIndex, address voter) external view returns (uint256 blockNumber);
+        function proposalStartBlock(uint256 proposalIndex) external view returns (uint256 blockNumber);
         function proposalEndBlock(uint256 proposalIndex) external view returns (uint256 blockNumber);
         function proposalExists(uint256 proposalIndex) external view returns (bool);
     }
@@ -65,14 +79,214 @@ sol! {
         Commitment commitment;
         uint proposalId;
         uint proposalEnd;
+        uint snapshotBlock;
         bool voted;
         address delegate;
         address claimant;
         uint votingPower;
+        uint quadraticPower;
         uint totalSupply;
         address votingToken;
         address governance;
+        bool finalized;
+        uint chainId;
+    }
+
+    /// Journal committed by the batch guest. Instead of a single voting power it
+    /// commits the root of a Merkle distribution tree over every claimant, so a
+    /// reward contract can settle the whole electorate with O(log n) claims.
+    struct BatchJournal {
+        Commitment commitment;
+        uint proposalId;
+        uint proposalEnd;
+        uint snapshotBlock;
+        bytes32 merkleRoot;
+        address votingToken;
+        address governance;
+        bool finalized;
+        uint chainId;
+    }
+}
+
+/// Resolve a chain id to its [`EthChainSpec`] so the tool can prove rewards on networks
+/// other than Sepolia. The guest resolves the same id independently, so the two specs
+/// always agree.
+fn chain_spec(chain_id: u64) -> Result<&'static EthChainSpec> {
+    Ok(match chain_id {
+        1 => &ETH_MAINNET_CHAIN_SPEC,
+        11155111 => &ETH_SEPOLIA_CHAIN_SPEC,
+        17000 => &ETH_HOLESKY_CHAIN_SPEC,
+        other => bail!("unsupported chain id {other}"),
+    })
+}
+
+/// Compute the distribution leaf for a claimant: `keccak256(abi.encode(claimant, votingPower))`.
+fn merkle_leaf(claimant: Address, voting_power: U256) -> B256 {
+    keccak256((claimant, voting_power).abi_encode())
+}
+
+/// Minimal consensus-layer client used to gate proving on beacon-chain finality.
+///
+/// When committing to a beacon or historical block we must ensure that block cannot be
+/// reorged out from under the EIP-4788 root the proof depends on, which is only
+/// guaranteed once it is at or below the finalized checkpoint.
+#[cfg(any(feature = "beacon", feature = "history"))]
+mod finality {
+    use anyhow::{Context, Result};
+    use serde::Deserialize;
+    use url::Url;
+
+    #[derive(Deserialize)]
+    struct FinalityCheckpoints {
+        data: FinalityData,
+    }
+
+    #[derive(Deserialize)]
+    struct FinalityData {
+        finalized: Checkpoint,
+    }
+
+    #[derive(Deserialize)]
+    struct Checkpoint {
+        epoch: String,
+    }
+
+    #[derive(Deserialize)]
+    struct BlockResponse {
+        data: BlockData,
+    }
+
+    #[derive(Deserialize)]
+    struct BlockData {
+        message: BlockMessage,
+    }
+
+    #[derive(Deserialize)]
+    struct BlockMessage {
+        body: BlockBody,
     }
+
+    #[derive(Deserialize)]
+    struct BlockBody {
+        execution_payload: ExecutionPayload,
+    }
+
+    #[derive(Deserialize)]
+    struct ExecutionPayload {
+        block_number: String,
+    }
+
+    /// The finalized boundary: the finalized epoch and the execution block number embedded
+    /// in the finalized beacon block.
+    ///
+    /// The gate compares execution *block numbers* rather than beacon slots: the commitment
+    /// the proof depends on is an execution block, and the finalized beacon block carries the
+    /// exact finalized execution block number in its payload. Comparing numbers avoids
+    /// translating an execution block back to a slot (which beacon slots may skip), so a
+    /// single consistent source defines the boundary.
+    pub struct Finalized {
+        pub epoch: u64,
+        pub execution_block: u64,
+    }
+
+    /// Resolve the beacon chain's current finalized checkpoint.
+    pub async fn resolve(beacon_api_url: &Url) -> Result<Finalized> {
+        let client = reqwest::Client::new();
+
+        let checkpoints: FinalityCheckpoints = client
+            .get(beacon_api_url.join("eth/v1/beacon/states/head/finality_checkpoints")?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("failed to fetch finality checkpoints")?;
+        let epoch: u64 = checkpoints
+            .data
+            .finalized
+            .epoch
+            .parse()
+            .context("invalid finalized epoch")?;
+
+        let block: BlockResponse = client
+            .get(beacon_api_url.join("eth/v2/beacon/blocks/finalized")?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("failed to fetch finalized block")?;
+        let execution_block: u64 = block
+            .data
+            .message
+            .body
+            .execution_payload
+            .block_number
+            .parse()
+            .context("invalid finalized execution block number")?;
+
+        Ok(Finalized {
+            epoch,
+            execution_block,
+        })
+    }
+}
+
+/// Checked integer square root returning `floor(sqrt(v))` via Newton's method, matching
+/// the guest's `isqrt` so quadratic-mode leaves agree.
+fn isqrt(v: U256) -> U256 {
+    if v == U256::ZERO {
+        return U256::ZERO;
+    }
+    let two = U256::from(2);
+    let mut x = v;
+    // Seed `v / 2 + v % 2` (i.e. `ceil(v / 2)`) rather than `(v + 1) / 2` so the first
+    // iterate can't overflow at `U256::MAX`; the two are equal for every input.
+    let mut y = v / two + v % two;
+    while y < x {
+        x = y;
+        y = (x + v / x) / two;
+    }
+    x
+}
+
+/// Hash an internal node using OpenZeppelin's pairing convention: the two child
+/// hashes are sorted before hashing so `MerkleProof.verify` accepts the proofs.
+fn hash_pair(a: B256, b: B256) -> B256 {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    keccak256([lo.as_slice(), hi.as_slice()].concat())
+}
+
+/// Build the tree bottom-up, returning every level (leaves first, root last). An
+/// odd node on a level is promoted unchanged to the next level.
+fn merkle_levels(leaves: Vec<B256>) -> Vec<Vec<B256>> {
+    let mut levels = vec![leaves];
+    while levels.last().map_or(0, |l| l.len()) > 1 {
+        let current = levels.last().unwrap();
+        let mut next = Vec::with_capacity(current.len().div_ceil(2));
+        for pair in current.chunks(2) {
+            next.push(match pair {
+                [a, b] => hash_pair(*a, *b),
+                [a] => *a,
+                _ => unreachable!(),
+            });
+        }
+        levels.push(next);
+    }
+    levels
+}
+
+/// Collect the sibling hashes on the path from leaf `index` up to the root.
+fn merkle_proof(levels: &[Vec<B256>], mut index: usize) -> Vec<B256> {
+    let mut proof = Vec::new();
+    for level in &levels[..levels.len().saturating_sub(1)] {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            proof.push(level[sibling]);
+        }
+        index /= 2;
+    }
+    proof
 }
 
 /// Simple program to create a proof to increment the Counter contract.
@@ -86,6 +300,11 @@ struct Args {
     #[arg(long, env = "ETH_RPC_URL")]
     eth_rpc_url: Url,
 
+    /// Chain id selecting the chain specification (e.g. 1 mainnet, 11155111 Sepolia,
+    /// 17000 Holesky). Defaults to Sepolia.
+    #[arg(long, env = "CHAIN_ID", default_value_t = 11155111)]
+    chain_id: u64,
+
     /// Beacon API endpoint URL
     ///
     /// Steel uses a beacon block commitment instead of the execution block.
@@ -103,13 +322,48 @@ struct Args {
     #[arg(long, env = "COMMITMENT_BLOCK")]
     commitment_block: BlockNumberOrTag,
 
+    /// Allow committing to a block that is not yet beacon-chain finalized.
+    ///
+    /// By default proving aborts if the commitment block is above the finalized
+    /// checkpoint, since a reorg would invalidate the EIP-4788 root.
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    #[arg(long)]
+    allow_unfinalized: bool,
+
+    /// Snapshot voting power from ERC-5805 checkpoints (`getPastVotes`) at the
+    /// proposal's start block instead of reading the live `balanceOf` and
+    /// reconstructing delegation manually.
+    #[arg(long)]
+    votes: bool,
+
+    /// Commit `sqrt(votingPower)` so the reward contract can implement quadratic
+    /// distribution that dampens whale influence. A single-claimant journal carries it
+    /// next to the raw balance; batch Merkle leaves carry it in place of the raw balance.
+    #[arg(long)]
+    quadratic: bool,
+
     /// The index of the proposal
     #[arg(long)]
     proposal_id: u64,
 
     /// The address of the claimant to generate the proof for
     #[arg(long)]
-    claimant: Address,
+    claimant: Option<Address>,
+
+    /// Comma-separated list of claimants to include in a batch Merkle distribution.
+    ///
+    /// When set (or `--claimants-file` lists any address) the host proves every address in a
+    /// single `EthEvmEnv` and the guest commits a Merkle root instead of one power.
+    #[arg(long, value_delimiter = ',')]
+    claimants: Vec<Address>,
+
+    /// Path to a file of claimant addresses (one per line) for a batch distribution.
+    #[arg(long)]
+    claimants_file: Option<PathBuf>,
+
+    /// Where to write the per-claimant Merkle proof paths as JSON (defaults to stdout).
+    #[arg(long)]
+    proofs_out: Option<PathBuf>,
 
     /// Address of the proposal contract
     #[arg(long)]
@@ -126,6 +380,33 @@ async fn main() -> Result<()> {
     // Parse the command line arguments.
     let args = Args::try_parse()?;
 
+    // Resolve the set of claimants. A batch distribution is requested whenever the list
+    // and file together yield at least one address; otherwise we fall back to the single
+    // `--claimant` path.
+    let mut claimants = args.claimants.clone();
+    if let Some(path) = &args.claimants_file {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read claimants file {}", path.display()))?;
+        for line in contents.lines() {
+            let line = line.trim();
+            if !line.is_empty() {
+                claimants.push(line.parse().context("invalid claimant address")?);
+            }
+        }
+    }
+    let batch = !claimants.is_empty();
+    if !batch {
+        claimants.push(
+            args.claimant
+                .context("either --claimant or --claimants/--claimants-file is required")?,
+        );
+    } else {
+        ensure!(
+            args.claimant.is_none(),
+            "--claimant cannot be combined with a batch distribution"
+        );
+    }
+
     // Create an alloy provider for that private key and URL.
     let wallet = EthereumWallet::from(args.eth_wallet_private_key);
     let provider = ProviderBuilder::new()
@@ -147,7 +428,7 @@ async fn main() -> Result<()> {
 
     let mut env = builder.build().await?;
     //  The `with_chain_spec` method is used to specify the chain configuration.
-    env = env.with_chain_spec(&ETH_SEPOLIA_CHAIN_SPEC);
+    env = env.with_chain_spec(chain_spec(args.chain_id)?);
 
     // Preflight: proposalExists
     let pex_call = IProposal::proposalExistsCall {
@@ -176,74 +457,245 @@ async fn main() -> Result<()> {
     let voting_token_address: Address = vt_return._0;
     assert!(voting_token_address != address_zero);
 
-    // Preflight: votedAt for claimant
-    let va_call = IProposal::votedAtCall {
-        proposalIndex: args.proposal_id.into(),
-        voter: args.claimant,
-    };
-    let va_return = Contract::preflight(args.proposal_contract, &mut env)
-        .call_builder(&va_call)
-        .call()
-        .await?;
-    let voted_block: U256 = va_return.blockNumber;
-
-    let mut voted_directly = voted_block > U256::from(0);
-
-    // Preflight: delegates call if not voted directly
-    let mut delegate_address = address_zero;
-    if !voted_directly {
-        let d_call = IDelegation::delegatesCall {
-            account: args.claimant,
+    // Preflight: proposalStartBlock — the ERC-5805 snapshot timepoint. Only `--votes` mode
+    // needs it, so legacy `balanceOf` proposals that predate `proposalStartBlock` keep
+    // working; otherwise the snapshot timepoint is left at zero.
+    let snapshot_block: U256 = if args.votes {
+        let ps_call = IProposal::proposalStartBlockCall {
+            proposalIndex: args.proposal_id.into(),
         };
-        let d_return = Contract::preflight(voting_token_address, &mut env)
-            .call_builder(&d_call)
+        let ps_return = Contract::preflight(args.proposal_contract, &mut env)
+            .call_builder(&ps_call)
             .call()
             .await?;
-        delegate_address = d_return._0;
+        ps_return.blockNumber
+    } else {
+        U256::ZERO
+    };
 
-        if delegate_address != address_zero {
-            // Preflight: votedAt for delegate
-            let va_delegated_call = IProposal::votedAtCall {
+    // Preflight each claimant, recording the voting power so the host can rebuild the
+    // same Merkle tree the guest commits and emit the per-claimant proof paths.
+    let mut leaf_values: Vec<(Address, U256)> = Vec::with_capacity(claimants.len());
+    for &claimant in &claimants {
+        let (voting_power, voted) = if args.votes {
+            // Participation is still gated on `votedAt` (the guest re-checks it); only the
+            // delegation walk is dropped, because `getPastVotes` aggregates delegated weight.
+            let va_call = IProposal::votedAtCall {
                 proposalIndex: args.proposal_id.into(),
-                voter: delegate_address,
+                voter: claimant,
             };
-            Contract::preflight(args.proposal_contract, &mut env)
-                .call_builder(&va_delegated_call)
+            let va_return = Contract::preflight(args.proposal_contract, &mut env)
+                .call_builder(&va_call)
                 .call()
                 .await?;
-        }
+            let voted = va_return.blockNumber > U256::from(0);
+
+            // Snapshot mode: `getPastVotes` already aggregates self- and inbound-delegated
+            // balance at the snapshot block, so there is no separate delegation branch.
+            let gpv_call = IVotes::getPastVotesCall {
+                account: claimant,
+                timepoint: snapshot_block,
+            };
+            let gpv_return = Contract::preflight(voting_token_address, &mut env)
+                .call_builder(&gpv_call)
+                .call()
+                .await?;
+
+            let gpts_call = IVotes::getPastTotalSupplyCall {
+                timepoint: snapshot_block,
+            };
+            Contract::preflight(voting_token_address, &mut env)
+                .call_builder(&gpts_call)
+                .call()
+                .await?;
+
+            (gpv_return._0, voted)
+        } else {
+            // Preflight: votedAt for claimant
+            let va_call = IProposal::votedAtCall {
+                proposalIndex: args.proposal_id.into(),
+                voter: claimant,
+            };
+            let va_return = Contract::preflight(args.proposal_contract, &mut env)
+                .call_builder(&va_call)
+                .call()
+                .await?;
+            let voted_block: U256 = va_return.blockNumber;
+
+            let voted_directly = voted_block > U256::from(0);
+
+            // Preflight: delegates call if not voted directly
+            let mut voted = voted_directly;
+            let mut delegate_address = address_zero;
+            if !voted_directly {
+                let d_call = IDelegation::delegatesCall { account: claimant };
+                let d_return = Contract::preflight(voting_token_address, &mut env)
+                    .call_builder(&d_call)
+                    .call()
+                    .await?;
+                delegate_address = d_return._0;
+
+                if delegate_address != address_zero {
+                    // Preflight: votedAt for delegate
+                    let va_delegated_call = IProposal::votedAtCall {
+                        proposalIndex: args.proposal_id.into(),
+                        voter: delegate_address,
+                    };
+                    let va_delegated_return = Contract::preflight(args.proposal_contract, &mut env)
+                        .call_builder(&va_delegated_call)
+                        .call()
+                        .await?;
+                    voted = va_delegated_return.blockNumber > U256::from(0);
+                }
+            }
+
+            // Preflight: balanceOf for claimant
+            let bo_call = IERC20::balanceOfCall { account: claimant };
+            let bo_return = Contract::preflight(voting_token_address, &mut env)
+                .call_builder(&bo_call)
+                .call()
+                .await?;
+
+            // Preflight: totalSupply
+            let ts_call = IERC20::totalSupplyCall {};
+            Contract::preflight(voting_token_address, &mut env)
+                .call_builder(&ts_call)
+                .call()
+                .await?;
+
+            (bo_return._0, voted)
+        };
+
+        // The guest asserts participation for every claimant, so reject non-voters here
+        // before any proofs artifact is written — otherwise the emitted JSON would disagree
+        // with what the guest commits and proving would abort with an opaque panic.
+        ensure!(
+            voted,
+            "claimant {claimant} did not participate in proposal {}",
+            args.proposal_id
+        );
+        leaf_values.push((claimant, voting_power));
     }
 
-    // Preflight: balanceOf for claimant
-    let bo_call = IERC20::balanceOfCall {
-        account: args.claimant,
-    };
-    Contract::preflight(voting_token_address, &mut env)
-        .call_builder(&bo_call)
-        .call()
-        .await?;
+    // Gate proving on beacon-chain finality. A commitment to a non-finalized block can be
+    // reorged out, invalidating the EIP-4788 root the proof depends on. Absent the
+    // beacon/history features nothing is checked, so the commitment is a plain reorgable
+    // `blockhash` and must not claim finality: default to `false` and only flip it `true`
+    // when the finality check below actually runs.
+    #[allow(unused_mut)]
+    let mut finalized = false;
+    #[cfg(any(feature = "beacon", feature = "history"))]
+    {
+        use risc0_steel::alloy::eips::BlockNumberOrTag as AlloyBlockTag;
+
+        // The beacon commitment tracks the commitment block under `history`, otherwise the
+        // execution block.
+        #[cfg(feature = "history")]
+        let commitment_tag = args.commitment_block;
+        #[cfg(not(feature = "history"))]
+        let commitment_tag = args.execution_block;
+
+        // Steel's `BlockNumberOrTag` carries a `Parent` variant (the default for the
+        // execution block) that alloy's provider API has no equivalent for, so resolve it to
+        // `latest - 1` before querying; the other variants map across one-to-one.
+        let commitment_tag = match commitment_tag {
+            BlockNumberOrTag::Parent => {
+                let latest = provider.get_block_number().await?;
+                AlloyBlockTag::Number(latest.saturating_sub(1))
+            }
+            BlockNumberOrTag::Number(n) => AlloyBlockTag::Number(n),
+            BlockNumberOrTag::Latest => AlloyBlockTag::Latest,
+            BlockNumberOrTag::Finalized => AlloyBlockTag::Finalized,
+            BlockNumberOrTag::Safe => AlloyBlockTag::Safe,
+            BlockNumberOrTag::Earliest => AlloyBlockTag::Earliest,
+            BlockNumberOrTag::Pending => AlloyBlockTag::Pending,
+        };
 
-    // Preflight: totalSupply (note: you mistakenly called balanceOf for totalSupply — needs a different interface ideally)
-    let ts_call = IERC20::balanceOfCall {
-        account: args.claimant,
-    };
-    Contract::preflight(voting_token_address, &mut env)
-        .call_builder(&ts_call)
-        .call()
-        .await?;
+        let commitment_block = provider
+            .get_block_by_number(commitment_tag, false)
+            .await?
+            .context("commitment block not found")?;
+        let commitment_number = commitment_block.header.number;
+
+        let fin = finality::resolve(&args.beacon_api_url).await?;
+        log::info!(
+            "Beacon finalized epoch {} (execution block {})",
+            fin.epoch,
+            fin.execution_block
+        );
+
+        finalized = commitment_number <= fin.execution_block;
+        ensure!(
+            finalized || args.allow_unfinalized,
+            "commitment block {} is not finalized (finalized execution block {}); \
+             pass --allow-unfinalized to override",
+            commitment_number,
+            fin.execution_block
+        );
+    }
 
     // Finally, construct the input from the environment.
     // There are two options: Use EIP-4788 for verification by providing a Beacon API endpoint,
     // or use the regular `blockhash' opcode.
     let evm_input = env.into_input().await?;
 
-    // Create the steel proof.
+    // Build the host-side Merkle tree so we can emit the per-claimant proof paths. The
+    // guest independently recomputes this root inside the zkVM and commits it.
+    // In quadratic mode the leaf commits `sqrt(votingPower)`, matching the guest.
+    let leaves: Vec<B256> = leaf_values
+        .iter()
+        .map(|&(claimant, power)| {
+            let committed = if args.quadratic { isqrt(power) } else { power };
+            merkle_leaf(claimant, committed)
+        })
+        .collect();
+    let levels = merkle_levels(leaves.clone());
+    let merkle_root = levels.last().and_then(|l| l.first()).copied();
+
+    if batch {
+        let proofs: Vec<_> = leaf_values
+            .iter()
+            .enumerate()
+            .map(|(i, &(claimant, power))| {
+                json!({
+                    "claimant": claimant,
+                    "votingPower": power,
+                    "quadraticPower": if args.quadratic { isqrt(power) } else { power },
+                    "leaf": leaves[i],
+                    "proof": merkle_proof(&levels, i),
+                })
+            })
+            .collect();
+        let report = json!({
+            "proposalId": args.proposal_id,
+            "merkleRoot": merkle_root,
+            "claims": proofs,
+        });
+        let report = serde_json::to_string_pretty(&report)?;
+        match &args.proofs_out {
+            Some(path) => fs::write(path, &report)
+                .with_context(|| format!("failed to write proofs to {}", path.display()))?,
+            None => println!("{report}"),
+        }
+    }
+
+    // Create the steel proof. The guest ignores this value in batch mode but still reads it,
+    // so make the "at least one claimant" invariant explicit rather than relying on the
+    // single-mode branch having pushed one address.
+    ensure!(!claimants.is_empty(), "no claimants to prove");
+    let single_claimant = claimants[0];
     let prove_info = task::spawn_blocking(move || {
         let env = ExecutorEnv::builder()
             .write(&evm_input)?
             .write(&args.proposal_id)?
-            .write(&args.claimant)?
+            .write(&single_claimant)?
             .write(&args.proposal_contract)?
+            .write(&args.votes)?
+            .write(&batch)?
+            .write(&claimants)?
+            .write(&args.quadratic)?
+            .write(&finalized)?
+            .write(&args.chain_id)?
             .build()
             .unwrap();
 
@@ -259,9 +711,19 @@ async fn main() -> Result<()> {
     let receipt = prove_info.receipt;
     let journal = &receipt.journal.bytes;
 
-    // Decode and log the commitment
-    let journal = Journal::abi_decode(journal, true).context("invalid journal")?;
-    log::debug!("Steel commitment: {:?}", journal.commitment);
+    // Decode and log the commitment. A batch run commits a Merkle root rather than a
+    // single voting power, so it is decoded through `BatchJournal`.
+    if batch {
+        let journal = BatchJournal::abi_decode(journal, true).context("invalid journal")?;
+        log::debug!("Steel commitment: {:?}", journal.commitment);
+        ensure!(
+            Some(journal.merkleRoot) == merkle_root,
+            "guest Merkle root does not match host-computed root"
+        );
+    } else {
+        let journal = Journal::abi_decode(journal, true).context("invalid journal")?;
+        log::debug!("Steel commitment: {:?}", journal.commitment);
+    }
 
     // ABI encode the seal.
     let seal = encode_seal(&receipt).context("invalid receipt")?;
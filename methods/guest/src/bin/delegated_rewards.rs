@@ -15,10 +15,13 @@
 #![allow(unused_doc_comments)]
 #![no_main]
 
-use alloy_primitives::{address, Address, U256};
+use alloy_primitives::{address, keccak256, Address, B256, U256};
 use alloy_sol_types::{sol, SolValue};
 use risc0_steel::{
-    ethereum::{EthEvmInput, ETH_SEPOLIA_CHAIN_SPEC},
+    ethereum::{
+        EthChainSpec, EthEvmInput, ETH_HOLESKY_CHAIN_SPEC, ETH_MAINNET_CHAIN_SPEC,
+        ETH_SEPOLIA_CHAIN_SPEC,
+    },
     Commitment, Contract,
 };
 use risc0_zkvm::guest::env;
@@ -38,9 +41,16 @@ sol! {
         function delegates(address account) external view returns (address);
     }
 
+    /// ERC-5805 checkpointed voting power (as implemented by OpenZeppelin `ERC20Votes`).
+    interface IVotes {
+        function getPastVotes(address account, uint256 timepoint) external view returns (uint256);
+        function getPastTotalSupply(uint256 timepoint) external view returns (uint256);
+    }
+
     interface IProposal {
         function votingToken() external view returns (address);
         function votedAt(uint256 proposalIndex, address voter) external view returns (uint256 blockNumber);
+        function proposalStartBlock(uint256 proposalIndex) external view returns (uint256 blockNumber);
         function proposalEndBlock(uint256 proposalIndex) external view returns (uint256 blockNumber);
         function proposalExists(uint256 proposalIndex) external view returns (bool);
     }
@@ -52,13 +62,90 @@ sol! {
         Commitment commitment;
         uint proposalId;
         uint proposalEnd;
+        uint snapshotBlock;
         bool voted;
         address delegate;
         address claimant;
         uint votingPower;
+        uint quadraticPower;
         uint totalSupply;
         address votingToken;
         address governance;
+        bool finalized;
+        uint chainId;
+    }
+
+    /// Journal committed when proving a batch of claimants. Instead of a single voting
+    /// power it commits the root of a Merkle distribution tree so a reward contract can
+    /// settle the electorate with O(log n) claims.
+    struct BatchJournal {
+        Commitment commitment;
+        uint proposalId;
+        uint proposalEnd;
+        uint snapshotBlock;
+        bytes32 merkleRoot;
+        address votingToken;
+        address governance;
+        bool finalized;
+        uint chainId;
+    }
+}
+
+/// Compute the distribution leaf for a claimant: `keccak256(abi.encode(claimant, votingPower))`.
+fn merkle_leaf(claimant: Address, voting_power: U256) -> B256 {
+    keccak256((claimant, voting_power).abi_encode())
+}
+
+/// Hash an internal node using OpenZeppelin's pairing convention: the two child hashes
+/// are sorted before hashing so the on-chain `MerkleProof.verify` library accepts the
+/// proofs unchanged.
+fn hash_pair(a: B256, b: B256) -> B256 {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    keccak256([lo.as_slice(), hi.as_slice()].concat())
+}
+
+/// Fold the leaves into a single Merkle root, promoting an odd node unchanged.
+fn merkle_root(mut level: Vec<B256>) -> B256 {
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity(level.len().div_ceil(2));
+        for pair in level.chunks(2) {
+            next.push(match pair {
+                [a, b] => hash_pair(*a, *b),
+                [a] => *a,
+                _ => unreachable!(),
+            });
+        }
+        level = next;
+    }
+    level.first().copied().unwrap_or(B256::ZERO)
+}
+
+/// Checked integer square root returning `floor(sqrt(v))` via Newton's method. The
+/// iteration is deterministic and allocation-free, so it runs cheaply inside the zkVM.
+fn isqrt(v: U256) -> U256 {
+    if v == U256::ZERO {
+        return U256::ZERO;
+    }
+    let two = U256::from(2);
+    let mut x = v;
+    // Seed `v / 2 + v % 2` (i.e. `ceil(v / 2)`) rather than `(v + 1) / 2` so the first
+    // iterate can't overflow at `U256::MAX`; the two are equal for every input.
+    let mut y = v / two + v % two;
+    while y < x {
+        x = y;
+        y = (x + v / x) / two;
+    }
+    x
+}
+
+/// Resolve a chain id to its [`EthChainSpec`], matching the host's selection so the guest
+/// evaluates the contract calls against the correct network.
+fn chain_spec(chain_id: u64) -> &'static EthChainSpec {
+    match chain_id {
+        1 => &ETH_MAINNET_CHAIN_SPEC,
+        11155111 => &ETH_SEPOLIA_CHAIN_SPEC,
+        17000 => &ETH_HOLESKY_CHAIN_SPEC,
+        other => panic!("unsupported chain id {other}"),
     }
 }
 
@@ -67,10 +154,16 @@ fn main() {
     let proposal_id: U256 = env::read();
     let claimant: Address = env::read();
     let proposal_contract: Address = env::read();
+    let votes_mode: bool = env::read();
+    let batch_mode: bool = env::read();
+    let claimants: Vec<Address> = env::read();
+    let quadratic_mode: bool = env::read();
+    let finalized: bool = env::read();
+    let chain_id: u64 = env::read();
 
     let address_zero = Address::ZERO;
 
-    let env = input.into_env().with_chain_spec(&ETH_SEPOLIA_CHAIN_SPEC);
+    let env = input.into_env().with_chain_spec(chain_spec(chain_id));
     let block_number = env.header().clone_inner().number;
 
     // check the proposal exists
@@ -103,80 +196,196 @@ fn main() {
     let voting_token_address: Address = vt_return._0;
     assert!(voting_token_address != address_zero.clone());
 
-    // check if the claimant voted directly
-    let va_call = IProposal::votedAtCall {
-        proposalIndex: proposal_id,
-        voter: claimant,
-    };
-    let va_return = Contract::new(proposal_contract, &env)
-        .call_builder(&va_call)
-        .call();
-    let voted_block: U256 = va_return.blockNumber;
-
-    let voted_directly = voted_block > U256::from(0) && voted_block < U256::from(block_number);
-
-    let mut voted = voted_directly;
-    let mut delegate_address = address_zero.clone();
-    // we can skip the delegation block if the user voted dirctly
-    if !voted_directly {
-        // if the user delegated, we need to check the delegate voting address too
-        // Fetch delegate address for claimant
-        let d_call = IDelegation::delegatesCall { account: claimant };
-        let d_return = Contract::new(voting_token_address, &env)
-            .call_builder(&d_call)
+    // Fetch the proposal's snapshot timepoint (its start block). ERC-5805 checkpoints
+    // are queried at this block so every voter's weight is fixed regardless of any
+    // balance or delegation changes between voting and claiming. Only `votes_mode` needs
+    // it, so legacy `balanceOf` proposals that predate `proposalStartBlock` still work; the
+    // committed `snapshotBlock` is left zero otherwise.
+    let snapshot_block: U256 = if votes_mode {
+        let ps_call = IProposal::proposalStartBlockCall {
+            proposalIndex: proposal_id,
+        };
+        let ps_return = Contract::new(proposal_contract, &env)
+            .call_builder(&ps_call)
             .call();
+        ps_return.blockNumber
+    } else {
+        U256::ZERO
+    };
 
-        // update the delegate_address
-        delegate_address = d_return._0;
+    // Compute a single claimant's voting power, returning `(voted, delegate, votingPower,
+    // totalSupply)`. Shared by the single- and batch-proof paths.
+    let voting_power_of = |claimant: Address| -> (bool, Address, U256, U256) {
+        if votes_mode {
+            // Snapshot mode: the weight is fixed at the proposal's snapshot block, which must
+            // lie in the past relative to the commitment block for a checkpoint to exist.
+            assert!(snapshot_block < U256::from(block_number));
 
-        let is_delegated = delegate_address != address_zero.clone();
+            // The request only dropped the *delegation* walk, not the participation check:
+            // `getPastVotes` aggregates delegated weight, but a claimant who never voted on
+            // this proposal must still be excluded. Gate on `votedAt` as the legacy path did.
+            let va_call = IProposal::votedAtCall {
+                proposalIndex: proposal_id,
+                voter: claimant,
+            };
+            let va_return = Contract::new(proposal_contract, &env)
+                .call_builder(&va_call)
+                .call();
+            let voted_block: U256 = va_return.blockNumber;
+            let voted = voted_block > U256::from(0) && voted_block < U256::from(block_number);
+
+            // `getPastVotes` already aggregates self- and inbound-delegated balance at the
+            // snapshot, so there is no separate delegation branch to walk.
+            let gpv_call = IVotes::getPastVotesCall {
+                account: claimant,
+                timepoint: snapshot_block,
+            };
+            let gpv_return = Contract::new(voting_token_address, &env)
+                .call_builder(&gpv_call)
+                .call();
+
+            let gpts_call = IVotes::getPastTotalSupplyCall {
+                timepoint: snapshot_block,
+            };
+            let gpts_return = Contract::new(voting_token_address, &env)
+                .call_builder(&gpts_call)
+                .call();
 
-        if is_delegated {
-            // Fetch block number when delegate voted
-            let va_delegated_call = IProposal::votedAtCall {
+            (voted, address_zero, gpv_return._0, gpts_return._0)
+        } else {
+            // check if the claimant voted directly
+            let va_call = IProposal::votedAtCall {
                 proposalIndex: proposal_id,
-                voter: delegate_address,
+                voter: claimant,
             };
-            let va_delegated_return = Contract::new(proposal_contract, &env)
-                .call_builder(&va_delegated_call)
+            let va_return = Contract::new(proposal_contract, &env)
+                .call_builder(&va_call)
                 .call();
-            let delegate_voted_block: U256 = va_delegated_return.blockNumber;
+            let voted_block: U256 = va_return.blockNumber;
 
-            // update the voted
-            voted = delegate_voted_block > U256::from(0)
-                && delegate_voted_block < U256::from(block_number);
-        }
-    }
+            let voted_directly =
+                voted_block > U256::from(0) && voted_block < U256::from(block_number);
 
-    // if the user didn't vote or delegate vote, we aren't creating a proof for them
-    assert!(voted);
+            let mut voted = voted_directly;
+            let mut delegate = address_zero;
+            // we can skip the delegation block if the user voted dirctly
+            if !voted_directly {
+                // if the user delegated, we need to check the delegate voting address too
+                // Fetch delegate address for claimant
+                let d_call = IDelegation::delegatesCall { account: claimant };
+                let d_return = Contract::new(voting_token_address, &env)
+                    .call_builder(&d_call)
+                    .call();
 
-    // Fetch claimant balance
-    let bo_call = IERC20::balanceOfCall { account: claimant };
-    let bo_return = Contract::new(voting_token_address, &env)
-        .call_builder(&bo_call)
-        .call();
-    let claimant_balance: U256 = bo_return._0;
+                // update the delegate_address
+                delegate = d_return._0;
 
-    // fetch the totalSupply
-    let ts_call = IERC20::totalSupplyCall {};
-    let ts_return = Contract::new(voting_token_address, &env)
-        .call_builder(&ts_call)
-        .call();
-    let total_supply: U256 = ts_return._0;
-
-    // Commit the block hash and number used when deriving `view_call_env` to the journal.
-    let journal = Journal {
-        commitment: env.into_commitment(),
-        proposalId: proposal_id,
-        proposalEnd: proposal_end_block,
-        voted: voted,
-        delegate: delegate_address,
-        claimant: claimant,
-        votingPower: claimant_balance,
-        totalSupply: total_supply,
-        votingToken: voting_token_address,
-        governance: proposal_contract,
+                let is_delegated = delegate != address_zero;
+
+                if is_delegated {
+                    // Fetch block number when delegate voted
+                    let va_delegated_call = IProposal::votedAtCall {
+                        proposalIndex: proposal_id,
+                        voter: delegate,
+                    };
+                    let va_delegated_return = Contract::new(proposal_contract, &env)
+                        .call_builder(&va_delegated_call)
+                        .call();
+                    let delegate_voted_block: U256 = va_delegated_return.blockNumber;
+
+                    // update the voted
+                    voted = delegate_voted_block > U256::from(0)
+                        && delegate_voted_block < U256::from(block_number);
+                }
+            }
+
+            // Fetch claimant balance
+            let bo_call = IERC20::balanceOfCall { account: claimant };
+            let bo_return = Contract::new(voting_token_address, &env)
+                .call_builder(&bo_call)
+                .call();
+
+            // fetch the totalSupply
+            let ts_call = IERC20::totalSupplyCall {};
+            let ts_return = Contract::new(voting_token_address, &env)
+                .call_builder(&ts_call)
+                .call();
+
+            (voted, delegate, bo_return._0, ts_return._0)
+        }
     };
-    env::commit_slice(&journal.abi_encode());
+
+    if batch_mode {
+        // Build a Merkle distribution tree over every claimant and commit only its root,
+        // so a reward contract can settle the electorate with O(log n) Merkle claims.
+        let leaves: Vec<B256> = claimants
+            .iter()
+            .map(|&claimant| {
+                let (voted, _, voting_power, _) = voting_power_of(claimant);
+                // every claimant must have voted or delegated their vote; a non-voter
+                // aborts the whole batch proof
+                assert!(voted);
+                // In quadratic mode the leaf commits `sqrt(votingPower)` to dampen whales.
+                let committed = if quadratic_mode {
+                    isqrt(voting_power)
+                } else {
+                    voting_power
+                };
+                merkle_leaf(claimant, committed)
+            })
+            .collect();
+        let root = merkle_root(leaves);
+
+        let journal = BatchJournal {
+            commitment: env.into_commitment(),
+            proposalId: proposal_id,
+            proposalEnd: proposal_end_block,
+            snapshotBlock: snapshot_block,
+            merkleRoot: root,
+            votingToken: voting_token_address,
+            governance: proposal_contract,
+            finalized: finalized,
+            chainId: U256::from(chain_id),
+        };
+        env::commit_slice(&journal.abi_encode());
+    } else {
+        let (voted, delegate_address, voting_power, total_supply) = voting_power_of(claimant);
+
+        // if the user didn't vote or delegate vote, we aren't creating a proof for them
+        assert!(voted);
+
+        // In quadratic mode we additionally commit `sqrt(votingPower)` so a downstream
+        // contract can do quadratic distribution while verifiers can still audit the raw
+        // balance against the transform. The committed total supply is replaced by its
+        // square root; the raw total supply is not committed in this mode.
+        let quadratic_power = if quadratic_mode {
+            isqrt(voting_power)
+        } else {
+            voting_power
+        };
+        let total_supply = if quadratic_mode {
+            isqrt(total_supply)
+        } else {
+            total_supply
+        };
+
+        // Commit the block hash and number used when deriving `view_call_env` to the journal.
+        let journal = Journal {
+            commitment: env.into_commitment(),
+            proposalId: proposal_id,
+            proposalEnd: proposal_end_block,
+            snapshotBlock: snapshot_block,
+            voted: voted,
+            delegate: delegate_address,
+            claimant: claimant,
+            votingPower: voting_power,
+            quadraticPower: quadratic_power,
+            totalSupply: total_supply,
+            votingToken: voting_token_address,
+            governance: proposal_contract,
+            finalized: finalized,
+            chainId: U256::from(chain_id),
+        };
+        env::commit_slice(&journal.abi_encode());
+    }
 }